@@ -1,15 +1,30 @@
-use clap::Parser;
-use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
+use clap::{Parser, ValueEnum};
+use directories::ProjectDirs;
+use glob::Pattern;
+use notify::{Config as WatcherConfig, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Deserialize;
 use std::{
-    io::{self, Read, Write},
-    path::PathBuf,
+    collections::HashMap,
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
     process::{Child, Command, Stdio},
-    sync::mpsc::{channel, Sender},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
     thread,
     time::{Duration, Instant},
 };
 
-#[derive(Parser, Debug)]
+const CONFIG_FILE_NAME: &str = "flutter_auto_reload.toml";
+const DEFAULT_DEBOUNCE_MS: u64 = 1000;
+const MAX_RESPAWN_ATTEMPTS: u32 = 5;
+const RESPAWN_BASE_DELAY: Duration = Duration::from_millis(500);
+const RESPAWN_ATTEMPT_RESET_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Parser, Debug, Clone)]
 #[command(author, version, about = "Auto-reload Flutter on file changes")]
 struct Args {
     /// Path to Flutter project
@@ -20,9 +35,9 @@ struct Args {
     #[arg(last = true)]
     flutter_args: Vec<String>,
 
-    /// Debounce duration in milliseconds
-    #[arg(long, default_value = "1000")]
-    debounce: u64,
+    /// Debounce duration in milliseconds (overrides the config file)
+    #[arg(long)]
+    debounce: Option<u64>,
 
     /// Device ID to run on
     #[arg(short = 'd', long)]
@@ -39,65 +54,596 @@ struct Args {
     /// Profile mode
     #[arg(long)]
     profile: bool,
+
+    /// Glob pattern to watch (repeatable, default "**/*.dart")
+    #[arg(long = "watch")]
+    watch_globs: Vec<String>,
+
+    /// Glob pattern to ignore (repeatable)
+    #[arg(long = "ignore")]
+    ignore_globs: Vec<String>,
+
+    /// Skip file-triggered reloads/restarts while the previous one has not yet
+    /// reported completion; skipped ones are not sent later
+    #[arg(long)]
+    wait_for_completion: bool,
+
+    /// Shell command to run whenever a watched file changes
+    #[arg(long = "on-change")]
+    on_change: Option<String>,
+
+    /// Shell command to run right before sending a reload/restart
+    #[arg(long = "pre-reload")]
+    pre_reload: Option<String>,
+
+    /// Shell command to run right after a reload/restart is sent
+    #[arg(long = "post-reload")]
+    post_reload: Option<String>,
+
+    /// How hook commands are invoked: spawn (fire-and-forget), capture
+    /// (collect output, print it on failure), or blocking (wait, and skip
+    /// the reload if the hook exits non-zero)
+    #[arg(long = "hook-mode", value_enum, default_value = "blocking")]
+    hook_mode: SubprocessCallMode,
+
+    /// Disable automatically respawning `flutter run` if it exits unexpectedly
+    #[arg(long)]
+    no_restart_on_exit: bool,
+}
+
+/// Mirrors joshuto's `SubprocessCallMode`: how a hook command is run
+/// relative to the reload it's attached to.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum SubprocessCallMode {
+    Spawn,
+    Capture,
+    Blocking,
+}
+
+/// The `--on-change`/`--pre-reload`/`--post-reload` hooks, resolved once at
+/// startup.
+struct HookConfig {
+    on_change: Option<String>,
+    pre_reload: Option<String>,
+    post_reload: Option<String>,
+    mode: SubprocessCallMode,
+}
+
+impl HookConfig {
+    fn from_args(args: &Args) -> Self {
+        Self {
+            on_change: args.on_change.clone(),
+            pre_reload: args.pre_reload.clone(),
+            post_reload: args.post_reload.clone(),
+            mode: args.hook_mode,
+        }
+    }
+}
+
+fn shell_command(cmd: &str) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(cmd);
+    command
+}
+
+/// Runs a hook command under the given call mode. Returns `false` only when
+/// a `Blocking` hook fails, signaling the caller to skip the reload.
+fn run_hook(label: &str, cmd: &str, mode: SubprocessCallMode) -> bool {
+    match mode {
+        SubprocessCallMode::Spawn => {
+            let cmd = cmd.to_string();
+            let label = label.to_string();
+            // Fire-and-forget still needs to reap the child once it exits,
+            // or every invocation leaks a zombie process.
+            thread::spawn(move || match shell_command(&cmd).spawn() {
+                Ok(mut child) => {
+                    child.wait().ok();
+                }
+                Err(err) => eprintln!("⚠️  hook '{label}' failed to spawn: {err}"),
+            });
+            true
+        }
+        SubprocessCallMode::Capture => match shell_command(cmd).output() {
+            Ok(output) if !output.status.success() => {
+                eprintln!("⚠️  hook '{label}' exited with {}", output.status);
+                io::stdout().write_all(&output.stdout).ok();
+                io::stderr().write_all(&output.stderr).ok();
+                true
+            }
+            Ok(_) => true,
+            Err(err) => {
+                eprintln!("⚠️  hook '{label}' failed to run: {err}");
+                true
+            }
+        },
+        SubprocessCallMode::Blocking => match shell_command(cmd).status() {
+            Ok(status) if status.success() => true,
+            Ok(status) => {
+                eprintln!("⚠️  hook '{label}' exited with {status}, skipping reload");
+                false
+            }
+            Err(err) => {
+                eprintln!("⚠️  hook '{label}' failed to run: {err}, skipping reload");
+                false
+            }
+        },
+    }
+}
+
+/// Persisted settings loaded from `flutter_auto_reload.toml`. Every field is
+/// optional so a partial file only overrides the defaults it mentions, and a
+/// CLI flag always takes precedence over the matching config value.
+#[derive(Debug, Default, Clone, Deserialize)]
+struct AppConfig {
+    debounce: Option<u64>,
+    device_id: Option<String>,
+    flavor: Option<String>,
+    release: Option<bool>,
+    profile: Option<bool>,
+    watch: Option<Vec<String>>,
+    ignore: Option<Vec<String>>,
+}
+
+/// Resolves the `flutter_auto_reload.toml` to use: a project-local file
+/// takes priority, falling back to the platform config dir.
+fn config_file_path(project_path: &Path) -> PathBuf {
+    let local = project_path.join(CONFIG_FILE_NAME);
+    if local.exists() {
+        return local;
+    }
+
+    if let Some(dirs) = ProjectDirs::from("dev", "EsinShadrach", "flutter_auto_reload") {
+        let platform = dirs.config_dir().join(CONFIG_FILE_NAME);
+        if platform.exists() {
+            return platform;
+        }
+    }
+
+    local
+}
+
+/// Resolves a path to an absolute, `.`-free form so paths built from
+/// different bases (e.g. `notify`'s event paths vs. our own
+/// `project_path.join(...)`) can be compared for identity. Falls back to a
+/// best-effort absolute path when the target doesn't exist yet, since
+/// `fs::canonicalize` requires the path to exist.
+fn normalize_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| {
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir()
+                .map(|cwd| cwd.join(path))
+                .unwrap_or_else(|_| path.to_path_buf())
+        };
+        absolute.components().collect()
+    })
+}
+
+fn paths_match(a: &Path, b: &Path) -> bool {
+    normalize_path(a) == normalize_path(b)
+}
+
+/// Loads the config file at startup, falling back to defaults when the file
+/// is missing or malformed. Only a malformed file is logged.
+fn load_config(path: &Path) -> AppConfig {
+    match fs::read_to_string(path) {
+        Ok(contents) => match toml::from_str(&contents) {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("Warning: failed to parse {}: {err}", path.display());
+                AppConfig::default()
+            }
+        },
+        Err(_) => AppConfig::default(),
+    }
+}
+
+/// Holds every config version seen so far behind a version counter, so
+/// readers on other threads can pick up the latest value without locking
+/// out a writer mid-update. Modeled on the versioned store used by
+/// clia-rcproxy for its live-reloaded routing config.
+struct ConfigStore {
+    version: AtomicUsize,
+    versions: Mutex<HashMap<usize, AppConfig>>,
+}
+
+impl ConfigStore {
+    fn new(initial: AppConfig) -> Self {
+        let mut versions = HashMap::new();
+        versions.insert(0, initial);
+        Self {
+            version: AtomicUsize::new(0),
+            versions: Mutex::new(versions),
+        }
+    }
+
+    fn current(&self) -> AppConfig {
+        let version = self.version.load(Ordering::Acquire);
+        self.versions
+            .lock()
+            .unwrap()
+            .get(&version)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn publish(&self, config: AppConfig) {
+        let next_version = self.version.load(Ordering::Acquire) + 1;
+        self.versions.lock().unwrap().insert(next_version, config);
+        self.version.store(next_version, Ordering::Release);
+    }
+}
+
+/// Re-reads the config file after a change event. Invalid TOML is logged
+/// and the previous version is kept rather than published, so a typo in the
+/// config file can't take down an in-progress session.
+fn reload_config_file(path: &Path, store: &ConfigStore) {
+    match fs::read_to_string(path) {
+        Ok(contents) => match toml::from_str::<AppConfig>(&contents) {
+            Ok(config) => {
+                println!("\n🛠️  Config reloaded from {}", path.display());
+                store.publish(config);
+            }
+            Err(err) => {
+                eprintln!(
+                    "Warning: failed to parse {}: {err} (keeping previous config)",
+                    path.display()
+                );
+            }
+        },
+        Err(err) => {
+            eprintln!(
+                "Warning: failed to read {}: {err} (keeping previous config)",
+                path.display()
+            );
+        }
+    }
+}
+
+/// Compiled include/exclude glob rules used to decide whether a changed
+/// path should trigger a reload.
+struct WatchFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl WatchFilter {
+    fn from_args_and_config(args: &Args, config: &AppConfig) -> Self {
+        let watch_globs = if !args.watch_globs.is_empty() {
+            args.watch_globs.clone()
+        } else if let Some(watch) = &config.watch {
+            watch.clone()
+        } else {
+            vec!["**/*.dart".to_string()]
+        };
+
+        let ignore_globs = if !args.ignore_globs.is_empty() {
+            args.ignore_globs.clone()
+        } else {
+            config.ignore.clone().unwrap_or_default()
+        };
+
+        let default_ignores = [
+            "**/build/**",
+            "**/.dart_tool/**",
+            "**/*.g.dart",
+        ];
+
+        let include = compile_patterns(&watch_globs);
+        let mut exclude = compile_patterns(&ignore_globs);
+        exclude.extend(compile_patterns(&default_ignores.map(String::from)));
+
+        Self { include, exclude }
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        self.exclude.iter().any(|p| p.matches_path(path))
+    }
+
+    fn is_included(&self, path: &Path) -> bool {
+        self.include.iter().any(|p| p.matches_path(path))
+    }
+}
+
+fn compile_patterns(globs: &[String]) -> Vec<Pattern> {
+    globs
+        .iter()
+        .filter_map(|glob| match Pattern::new(glob) {
+            Ok(pattern) => Some(pattern),
+            Err(err) => {
+                eprintln!("Warning: invalid glob pattern '{glob}': {err}");
+                None
+            }
+        })
+        .collect()
 }
 
 enum FlutterCommand {
     Reload,
+    Restart,
     KeyInput(u8),
 }
 
+/// Flutter's resident runner can't pick up pubspec changes, native project
+/// files, or newly added/removed assets with a hot reload; those need a
+/// full hot restart instead so the asset manifest gets rebuilt.
+fn is_restart_trigger(path: &Path) -> bool {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    if file_name == "pubspec.yaml" || file_name == "pubspec.lock" {
+        return true;
+    }
+
+    path.components().any(|component| {
+        matches!(
+            component.as_os_str().to_str(),
+            Some("android") | Some("ios") | Some("assets")
+        )
+    })
+}
+
+/// Classifies a changed path into the flutter command it should trigger:
+/// `None` if an exclude glob matches, `Restart` for a restart trigger (even
+/// when no include glob matches it), `Reload` for an included path, and
+/// `None` otherwise.
+fn classify_change(path: &Path, watch_filter: &WatchFilter) -> Option<FlutterCommand> {
+    if watch_filter.is_excluded(path) {
+        return None;
+    }
+
+    if is_restart_trigger(path) {
+        return Some(FlutterCommand::Restart);
+    }
+
+    if watch_filter.is_included(path) {
+        return Some(FlutterCommand::Reload);
+    }
+
+    None
+}
+
+/// A completion marker scanned out of flutter's stdout, used to time how
+/// long a reload/restart actually took to land.
+enum CompletionKind {
+    Reload,
+    Restart,
+    Recompile,
+}
+
+fn classify_output_line(line: &str) -> Option<CompletionKind> {
+    if line.contains("Reloaded application") {
+        Some(CompletionKind::Reload)
+    } else if line.contains("Restarted application") {
+        Some(CompletionKind::Restart)
+    } else if line.contains("Recompile complete") {
+        Some(CompletionKind::Recompile)
+    } else {
+        None
+    }
+}
+
+/// Tees the child's stdout to our own stdout line by line, and forwards any
+/// completion marker it recognizes so the main loop can time it.
+fn spawn_stdout_reader(stdout: std::process::ChildStdout, tx: Sender<CompletionKind>) {
+    thread::spawn(move || {
+        let reader = BufReader::new(stdout);
+        for line in reader.lines() {
+            match line {
+                Ok(line) => {
+                    println!("{line}");
+                    if let Some(kind) = classify_output_line(&line) {
+                        tx.send(kind).ok();
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+    });
+}
+
+#[derive(Default)]
+struct ReloadMetrics {
+    reload_count: u32,
+    restart_count: u32,
+}
+
 struct FlutterRunner {
     process: Child,
     last_reload: Instant,
-    debounce_duration: Duration,
+    cli_debounce_override: Option<u64>,
+    config: Arc<ConfigStore>,
+    wait_for_completion: bool,
+    pending_since: Option<Instant>,
+    metrics: ReloadMetrics,
+    completion_rx: Receiver<CompletionKind>,
+    args: Args,
+    restart_on_exit: bool,
+    respawn_attempts: u32,
+    process_started_at: Instant,
 }
 
 impl FlutterRunner {
-    fn new(args: &Args) -> std::io::Result<Self> {
+    fn spawn_process(
+        args: &Args,
+        config: &AppConfig,
+    ) -> std::io::Result<(Child, Receiver<CompletionKind>)> {
         let mut command = Command::new("flutter");
         command.arg("run");
 
-        if let Some(device_id) = &args.device_id {
+        let device_id = args.device_id.clone().or_else(|| config.device_id.clone());
+        if let Some(device_id) = &device_id {
             command.arg("--device-id").arg(device_id);
         }
 
-        if let Some(flavor) = &args.flavor {
+        let flavor = args.flavor.clone().or_else(|| config.flavor.clone());
+        if let Some(flavor) = &flavor {
             command.arg("--flavor").arg(flavor);
         }
 
-        if args.release {
+        let release = args.release || config.release.unwrap_or(false);
+        let profile = args.profile || config.profile.unwrap_or(false);
+        if release {
             command.arg("--release");
-        } else if args.profile {
+        } else if profile {
             command.arg("--profile");
         }
 
         command.args(&args.flutter_args);
 
-        let process = command
+        let mut process = command
             .stdin(Stdio::piped())
-            .stdout(Stdio::inherit())
+            .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
             .current_dir(&args.project_path)
             .spawn()?;
 
+        let (completion_tx, completion_rx) = channel();
+        let stdout = process.stdout.take().expect("stdout was piped");
+        spawn_stdout_reader(stdout, completion_tx);
+
+        Ok((process, completion_rx))
+    }
+
+    fn new(args: &Args, config: &AppConfig, config_store: Arc<ConfigStore>) -> std::io::Result<Self> {
+        let (process, completion_rx) = Self::spawn_process(args, config)?;
+
         Ok(Self {
             process,
             last_reload: Instant::now(),
-            debounce_duration: Duration::from_millis(args.debounce),
+            cli_debounce_override: args.debounce,
+            config: config_store,
+            wait_for_completion: args.wait_for_completion,
+            pending_since: None,
+            metrics: ReloadMetrics::default(),
+            completion_rx,
+            args: args.clone(),
+            restart_on_exit: !args.no_restart_on_exit,
+            respawn_attempts: 0,
+            process_started_at: Instant::now(),
         })
     }
 
+    /// Checks whether `flutter run` has died and, in supervised mode,
+    /// respawns it from the original args plus the latest live-reloaded
+    /// config, with exponential backoff, giving up after
+    /// `MAX_RESPAWN_ATTEMPTS` to avoid a crash loop.
+    fn check_process_health(&mut self) -> std::io::Result<()> {
+        if !self.restart_on_exit {
+            return Ok(());
+        }
+
+        let Some(status) = self.process.try_wait()? else {
+            // Healthy and long-lived enough that earlier failures were
+            // likely unrelated transient blips: forgive them.
+            if self.respawn_attempts > 0
+                && self.process_started_at.elapsed() >= RESPAWN_ATTEMPT_RESET_AFTER
+            {
+                self.respawn_attempts = 0;
+            }
+            return Ok(());
+        };
+
+        if self.respawn_attempts >= MAX_RESPAWN_ATTEMPTS {
+            return Err(io::Error::other(format!(
+                "flutter run exited ({status}) and the retry limit of {MAX_RESPAWN_ATTEMPTS} was reached"
+            )));
+        }
+
+        let backoff = RESPAWN_BASE_DELAY * 2u32.pow(self.respawn_attempts);
+        eprintln!(
+            "⚠️  flutter run exited unexpectedly ({status}); respawning in {}ms...",
+            backoff.as_millis()
+        );
+        thread::sleep(backoff);
+
+        let (process, completion_rx) = Self::spawn_process(&self.args, &self.config.current())?;
+        self.process = process;
+        self.completion_rx = completion_rx;
+        self.last_reload = Instant::now();
+        self.pending_since = None;
+        self.respawn_attempts += 1;
+        self.process_started_at = Instant::now();
+        println!("✅ flutter run respawned");
+
+        Ok(())
+    }
+
+    /// Drains any completion markers the reader thread has picked up,
+    /// printing elapsed time and a running count for each.
+    fn poll_completions(&mut self) {
+        while let Ok(kind) = self.completion_rx.try_recv() {
+            match kind {
+                CompletionKind::Reload => {
+                    let elapsed = self.pending_since.take().map(|since| since.elapsed());
+                    self.metrics.reload_count += 1;
+                    Self::print_completion("Hot reload", elapsed, self.metrics.reload_count);
+                }
+                CompletionKind::Restart => {
+                    let elapsed = self.pending_since.take().map(|since| since.elapsed());
+                    self.metrics.restart_count += 1;
+                    Self::print_completion("Hot restart", elapsed, self.metrics.restart_count);
+                }
+                CompletionKind::Recompile => {
+                    // Flutter prints this before the Reload/Restart line, so
+                    // leave `pending_since` alone for that line to consume.
+                    println!("🧩 Recompile complete");
+                }
+            }
+        }
+    }
+
+    fn print_completion(label: &str, elapsed: Option<Duration>, count: u32) {
+        match elapsed {
+            Some(elapsed) => println!(
+                "✅ {label} completed in {}ms (total: {count})",
+                elapsed.as_millis()
+            ),
+            None => println!("✅ {label} completed (total: {count})"),
+        }
+    }
+
+    /// The debounce window in effect right now: a CLI override stays pinned
+    /// for the whole run, otherwise the latest published config version wins
+    /// without requiring a restart.
+    fn debounce_duration(&self) -> Duration {
+        let millis = self
+            .cli_debounce_override
+            .unwrap_or_else(|| self.config.current().debounce.unwrap_or(DEFAULT_DEBOUNCE_MS));
+        Duration::from_millis(millis)
+    }
+
     fn handle_command(&mut self, cmd: FlutterCommand) -> std::io::Result<()> {
         match cmd {
             FlutterCommand::Reload => {
+                if self.wait_for_completion && self.pending_since.is_some() {
+                    return Ok(());
+                }
+
                 let now = Instant::now();
-                if now.duration_since(self.last_reload) >= self.debounce_duration {
+                if now.duration_since(self.last_reload) >= self.debounce_duration() {
                     println!("\n🔄 Change detected, triggering hot reload...");
                     if let Some(stdin) = self.process.stdin.as_mut() {
                         stdin.write_all(b"r\n")?;
                         stdin.flush()?;
                     }
                     self.last_reload = now;
+                    self.pending_since = Some(now);
+                }
+            }
+            FlutterCommand::Restart => {
+                if self.wait_for_completion && self.pending_since.is_some() {
+                    return Ok(());
+                }
+
+                let now = Instant::now();
+                if now.duration_since(self.last_reload) >= self.debounce_duration() {
+                    println!("\n🔁 Change detected, triggering hot restart...");
+                    if let Some(stdin) = self.process.stdin.as_mut() {
+                        stdin.write_all(b"R\n")?;
+                        stdin.flush()?;
+                    }
+                    self.last_reload = now;
+                    self.pending_since = Some(now);
                 }
             }
             FlutterCommand::KeyInput(key) => {
@@ -149,13 +695,20 @@ fn main() -> std::io::Result<()> {
         println!("⚙️  Additional args: {}", args.flutter_args.join(" "));
     }
 
-    let mut flutter = FlutterRunner::new(&args)?;
+    let config_path = config_file_path(&args.project_path);
+    let initial_config = load_config(&config_path);
+    let config_store = Arc::new(ConfigStore::new(initial_config.clone()));
+
+    let watch_filter = WatchFilter::from_args_and_config(&args, &initial_config);
+    let hooks = HookConfig::from_args(&args);
+
+    let mut flutter = FlutterRunner::new(&args, &initial_config, Arc::clone(&config_store))?;
 
     // Channel for file watcher events
     let (file_tx, file_rx) = channel();
     let mut watcher = RecommendedWatcher::new(
         file_tx,
-        Config::default().with_poll_interval(Duration::from_secs(1)),
+        WatcherConfig::default().with_poll_interval(Duration::from_secs(1)),
     )
     .unwrap();
 
@@ -167,6 +720,14 @@ fn main() -> std::io::Result<()> {
         .watch(&args.project_path, RecursiveMode::Recursive)
         .unwrap();
 
+    // If the config file lives outside the project dir (the platform config
+    // dir case), watch its parent separately so edits to it are still seen.
+    if let Some(config_dir) = config_path.parent() {
+        if config_dir != args.project_path {
+            watcher.watch(config_dir, RecursiveMode::NonRecursive).ok();
+        }
+    }
+
     println!("✨ Auto-reload is now active. Watching for changes...");
     println!("💡 You can use all Flutter commands (r = reload, R = restart, h = help)");
 
@@ -177,13 +738,36 @@ fn main() -> std::io::Result<()> {
 
     // Main event loop
     loop {
+        // Supervise the flutter process, respawning it if it died
+        flutter.check_process_health()?;
+
         // Check for file changes
         if let Ok(event) = file_rx.try_recv() {
             if let Ok(event) = event {
                 if let Some(path) = event.paths.first() {
-                    if let Some(ext) = path.extension() {
-                        if ext == "dart" {
-                            flutter.handle_command(FlutterCommand::Reload)?;
+                    if paths_match(path, &config_path) {
+                        reload_config_file(&config_path, &config_store);
+                    } else if let Some(cmd) = classify_change(path, &watch_filter) {
+                        let on_change_ok = match &hooks.on_change {
+                            Some(hook) => run_hook("on-change", hook, hooks.mode),
+                            None => true,
+                        };
+
+                        let pre_reload_ok = match &hooks.pre_reload {
+                            Some(hook) => run_hook("pre-reload", hook, hooks.mode),
+                            None => true,
+                        };
+
+                        if on_change_ok && pre_reload_ok {
+                            let last_sent = flutter.last_reload;
+                            flutter.handle_command(cmd)?;
+                            // Debounce or wait-for-completion may have
+                            // suppressed the send; only run the hook if it went out.
+                            if flutter.last_reload != last_sent {
+                                if let Some(hook) = &hooks.post_reload {
+                                    run_hook("post-reload", hook, hooks.mode);
+                                }
+                            }
                         }
                     }
                 }
@@ -195,7 +779,138 @@ fn main() -> std::io::Result<()> {
             flutter.handle_command(cmd)?;
         }
 
+        // Pick up any reload/restart completion markers from flutter's stdout
+        flutter.poll_completions();
+
         // Small sleep to prevent busy waiting
         thread::sleep(Duration::from_millis(10));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patterns(globs: &[&str]) -> Vec<Pattern> {
+        compile_patterns(&globs.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    }
+
+    fn watch_filter(include: &[&str], exclude: &[&str]) -> WatchFilter {
+        WatchFilter {
+            include: patterns(include),
+            exclude: patterns(exclude),
+        }
+    }
+
+    #[test]
+    fn watch_filter_includes_dart_files_by_default() {
+        let filter = watch_filter(&["**/*.dart"], &[]);
+        assert!(filter.is_included(Path::new("lib/main.dart")));
+        assert!(!filter.is_included(Path::new("lib/main.arb")));
+    }
+
+    #[test]
+    fn watch_filter_excludes_generated_and_build_paths() {
+        let filter = watch_filter(&["**/*.dart"], &["**/build/**", "**/*.g.dart"]);
+        assert!(filter.is_excluded(Path::new("build/main.dart")));
+        assert!(filter.is_excluded(Path::new("lib/model.g.dart")));
+        assert!(!filter.is_excluded(Path::new("lib/model.dart")));
+    }
+
+    #[test]
+    fn classify_change_routes_dart_edits_to_reload() {
+        let filter = watch_filter(&["**/*.dart"], &[]);
+        assert!(matches!(
+            classify_change(Path::new("lib/main.dart"), &filter),
+            Some(FlutterCommand::Reload)
+        ));
+    }
+
+    #[test]
+    fn classify_change_routes_pubspec_and_native_dirs_to_restart() {
+        let filter = watch_filter(&["**/*.dart"], &[]);
+        assert!(matches!(
+            classify_change(Path::new("project/pubspec.yaml"), &filter),
+            Some(FlutterCommand::Restart)
+        ));
+        assert!(matches!(
+            classify_change(Path::new("project/android/app/build.gradle"), &filter),
+            Some(FlutterCommand::Restart)
+        ));
+        assert!(matches!(
+            classify_change(Path::new("project/assets/logo.png"), &filter),
+            Some(FlutterCommand::Restart)
+        ));
+    }
+
+    #[test]
+    fn classify_change_ignores_excluded_paths_even_if_restart_trigger() {
+        let filter = watch_filter(&["**/*.dart"], &["**/android/**"]);
+        assert!(classify_change(Path::new("project/android/app/build.gradle"), &filter).is_none());
+    }
+
+    #[test]
+    fn classify_change_returns_none_for_unmatched_paths() {
+        let filter = watch_filter(&["**/*.dart"], &[]);
+        assert!(classify_change(Path::new("README.md"), &filter).is_none());
+    }
+
+    #[test]
+    fn config_store_starts_at_the_initial_version() {
+        let store = ConfigStore::new(AppConfig {
+            debounce: Some(500),
+            ..AppConfig::default()
+        });
+        assert_eq!(store.current().debounce, Some(500));
+    }
+
+    #[test]
+    fn config_store_publish_bumps_the_version_readers_see() {
+        let store = ConfigStore::new(AppConfig::default());
+        assert_eq!(store.current().debounce, None);
+
+        store.publish(AppConfig {
+            debounce: Some(250),
+            ..AppConfig::default()
+        });
+
+        assert_eq!(store.current().debounce, Some(250));
+    }
+
+    #[test]
+    fn config_store_keeps_every_published_version() {
+        let store = ConfigStore::new(AppConfig::default());
+        store.publish(AppConfig {
+            debounce: Some(100),
+            ..AppConfig::default()
+        });
+        store.publish(AppConfig {
+            debounce: Some(200),
+            ..AppConfig::default()
+        });
+
+        assert_eq!(store.current().debounce, Some(200));
+        assert_eq!(store.versions.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn run_hook_blocking_succeeds_on_zero_exit() {
+        assert!(run_hook("test", "exit 0", SubprocessCallMode::Blocking));
+    }
+
+    #[test]
+    fn run_hook_blocking_fails_on_nonzero_exit() {
+        assert!(!run_hook("test", "exit 1", SubprocessCallMode::Blocking));
+    }
+
+    #[test]
+    fn run_hook_capture_never_blocks_the_reload() {
+        assert!(run_hook("test", "exit 1", SubprocessCallMode::Capture));
+        assert!(run_hook("test", "exit 0", SubprocessCallMode::Capture));
+    }
+
+    #[test]
+    fn run_hook_spawn_never_blocks_the_reload() {
+        assert!(run_hook("test", "exit 1", SubprocessCallMode::Spawn));
+    }
+}